@@ -9,6 +9,11 @@ use crate::pac::{rtc0, Interrupt, RTC0, RTC1};
 #[cfg(any(feature = "52832", feature = "52833", feature = "52840"))]
 use crate::pac::RTC2;
 
+// We reserve cc[0] for the 2^23 period-tracking midpoint, so alarm `n` is
+// backed by cc[n + 1]. The largest nRF RTC instance has four CC registers,
+// leaving room for three independent alarms.
+const ALARM_COUNT: usize = 3;
+
 fn calc_now(period: u32, counter: u32) -> u64 {
     let shift = ((period & 1) << 23) + 0x400000;
     let counter_shifted = (counter + shift) & 0xFFFFFF;
@@ -37,6 +42,13 @@ mod test {
 pub struct RTC<T> {
     rtc: T,
 
+    /// PRESCALER value written to the peripheral in `start()`.
+    ///
+    /// The tick frequency is `32768 / (prescaler + 1)`. The prescaler divides the
+    /// LFCLK input, not the 24-bit counter, so the period-midpoint scheme is
+    /// unaffected.
+    prescaler: u16,
+
     /// Number of 2^23 periods elapsed since boot.
     ///
     /// This is incremented by 1
@@ -46,26 +58,47 @@ pub struct RTC<T> {
     /// Therefore: When even, counter is in 0..0x7fffff. When odd, counter is in 0x800000..0xFFFFFF
     /// This allows for now() to return the correct value even if it races an overflow.
     ///
-    /// It overflows on 2^32 * 2^23 / 32768 seconds of uptime, which is 34865 years.
+    /// It overflows on 2^32 * 2^23 / freq seconds of uptime, which is 34865 years
+    /// at the default 32768 Hz; dividing the clock with the prescaler lengthens it
+    /// proportionally.
     period: AtomicU32,
 
-    /// Timestamp at which to fire alarm. u64::MAX if no alarm is scheduled.
-    alarm: Mutex<Cell<(u64, Option<fn()>)>>,
+    /// Timestamp at which to fire each alarm, and the callback to invoke.
+    ///
+    /// Indexed by alarm id: alarm `n` lives in slot `n` and is backed by cc[n + 1].
+    /// The timestamp is u64::MAX when no alarm is scheduled for that slot.
+    alarms: Mutex<Cell<[(u64, Option<fn()>); ALARM_COUNT]>>,
+
+    /// Optional callback fired on every true 2^24 counter wrap (not the midpoint
+    /// increment). Lets higher layers maintain RTC-backed calendar time or detect
+    /// missed wakeups without polling `now()`.
+    overflow_callback: Mutex<Cell<Option<fn()>>>,
 }
 
 unsafe impl<T> Send for RTC<T> {}
 unsafe impl<T> Sync for RTC<T> {}
 
 impl<T: Instance> RTC<T> {
-    pub fn new(rtc: T) -> Self {
+    /// Create a new RTC driver running at `32768 / (prescaler + 1)` Hz.
+    ///
+    /// `prescaler` is the raw value written to the 12-bit PRESCALER register, so
+    /// `0` keeps the raw 32768 Hz LFCLK and e.g. `31` gives 1024 Hz (~1 ms
+    /// resolution) for lower interrupt wakeup load.
+    pub fn new(rtc: T, prescaler: u16) -> Self {
         Self {
             rtc,
+            prescaler,
             period: AtomicU32::new(0),
-            alarm: Mutex::new(Cell::new((u64::MAX, None))),
+            alarms: Mutex::new(Cell::new([(u64::MAX, None); ALARM_COUNT])),
+            overflow_callback: Mutex::new(Cell::new(None)),
         }
     }
 
     pub fn start(&'static self) {
+        self.rtc
+            .prescaler
+            .write(|w| unsafe { w.prescaler().bits(self.prescaler) });
+
         self.rtc.cc[0].write(|w| unsafe { w.bits(0x800000) });
 
         self.rtc.intenset.write(|w| {
@@ -84,6 +117,44 @@ impl<T: Instance> RTC<T> {
         interrupt::enable(T::INTERRUPT);
     }
 
+    /// Halt the RTC and release the instance, reversing `start()`.
+    ///
+    /// Issues `tasks_stop`, disables all compare/overflow interrupt sources and
+    /// the NVIC line, resets the period counter, clears any pending alarms and
+    /// detaches the static instance, so the driver can be re-`start()`ed safely
+    /// (e.g. after deep sleep or to reconfigure the prescaler).
+    pub fn stop(&'static self) {
+        self.rtc.tasks_stop.write(|w| w.tasks_stop().set_bit());
+
+        self.rtc.intenclr.write(|w| {
+            let w = w.ovrflw().clear();
+            let w = w.compare0().clear();
+            w
+        });
+        for n in 0..T::ALARM_COUNT {
+            self.clear_compare_int(n + 1);
+        }
+
+        interrupt::disable(T::INTERRUPT);
+
+        self.period.store(0, Ordering::Relaxed);
+        interrupt::free(|cs| {
+            self.alarms
+                .borrow(cs)
+                .set([(u64::MAX, None); ALARM_COUNT]);
+        });
+
+        T::clear_rtc_instance();
+    }
+
+    /// Tick frequency in Hz, i.e. the rate at which `now()` advances.
+    ///
+    /// Callers converting between `embassy::time` ticks and wall-clock seconds
+    /// should use this rather than assuming 32768 Hz.
+    pub fn frequency(&self) -> u32 {
+        32_768 / (self.prescaler as u32 + 1)
+    }
+
     pub fn now(&self) -> u64 {
         let counter = self.rtc.counter.read().bits();
         let period = self.period.load(Ordering::Relaxed);
@@ -101,9 +172,11 @@ impl<T: Instance> RTC<T> {
             self.next_period();
         }
 
-        if self.rtc.events_compare[1].read().bits() == 1 {
-            self.rtc.events_compare[1].write(|w| w);
-            self.trigger_alarm();
+        for n in 0..T::ALARM_COUNT {
+            if self.rtc.events_compare[n + 1].read().bits() == 1 {
+                self.rtc.events_compare[n + 1].write(|w| w);
+                self.trigger_alarm(n);
+            }
         }
     }
 
@@ -112,42 +185,71 @@ impl<T: Instance> RTC<T> {
             let period = self.period.fetch_add(1, Ordering::Relaxed) + 1;
             let t = (period as u64) << 23;
 
-            let (at, _) = self.alarm.borrow(cs).get();
+            // An even period means the counter just wrapped at 0 (the true 2^24
+            // overflow); an odd period is the 0x800000 midpoint increment.
+            if period & 1 == 0 {
+                if let Some(f) = self.overflow_callback.borrow(cs).get() {
+                    f();
+                }
+            }
 
-            let diff = at - t;
-            if diff < 0xc00000 {
-                self.rtc.cc[1].write(|w| unsafe { w.bits(at as u32 & 0xFFFFFF) });
-                self.rtc.intenset.write(|w| w.compare1().set());
+            let alarms = self.alarms.borrow(cs).get();
+            for n in 0..T::ALARM_COUNT {
+                let (at, _) = alarms[n];
+
+                let diff = at - t;
+                if diff < 0xc00000 {
+                    self.rtc.cc[n + 1].write(|w| unsafe { w.bits(at as u32 & 0xFFFFFF) });
+                    self.set_compare_int(n + 1);
+                }
             }
         })
     }
 
-    fn trigger_alarm(&self) {
-        self.rtc.intenclr.write(|w| w.compare1().clear());
+    fn trigger_alarm(&self, n: usize) {
+        self.clear_compare_int(n + 1);
         interrupt::free(|cs| {
-            let alarm = self.alarm.borrow(cs);
-            let (_, f) = alarm.get();
-            alarm.set((u64::MAX, None));
+            let alarms = self.alarms.borrow(cs);
+            let mut slots = alarms.get();
+            let (_, f) = slots[n];
+            slots[n] = (u64::MAX, None);
+            alarms.set(slots);
 
             // Call after clearing alarm, so the callback can set another alarm.
             f.map(|f| f())
         });
     }
 
-    fn do_set_alarm(&self, timestamp: u64, callback: Option<fn()>) {
+    fn do_set_alarm(&self, n: usize, timestamp: u64, callback: Option<fn()>) {
         interrupt::free(|cs| {
-            self.alarm.borrow(cs).set((timestamp, callback));
+            let alarms = self.alarms.borrow(cs);
+            let mut slots = alarms.get();
+            slots[n] = (timestamp, callback);
+            alarms.set(slots);
 
             let t = self.now();
             if timestamp <= t {
-                self.trigger_alarm();
+                self.trigger_alarm(n);
                 return;
             }
 
             let diff = timestamp - t;
             if diff < 0xc00000 {
-                self.rtc.cc[1].write(|w| unsafe { w.bits(timestamp as u32 & 0xFFFFFF) });
-                self.rtc.intenset.write(|w| w.compare1().set());
+                let cc = timestamp as u32 & 0xFFFFFF;
+
+                // The RTC hardware won't raise a COMPARE event when CC is written
+                // equal to the current COUNTER (or COUNTER+1), which would make the
+                // alarm wait a full 2^24-tick wrap before firing. Enforce a minimum
+                // lead of two ticks: if the target is that close, treat it as already
+                // elapsed and fire immediately.
+                let counter = self.rtc.counter.read().bits();
+                if (cc.wrapping_sub(counter) & 0xFFFFFF) < 2 {
+                    self.trigger_alarm(n);
+                    return;
+                }
+
+                self.rtc.cc[n + 1].write(|w| unsafe { w.bits(cc) });
+                self.set_compare_int(n + 1);
 
                 // We may have been preempted for arbitrary time between checking if `at` is in the past
                 // and setting the cc. In that case, we don't know if the cc has triggered.
@@ -155,31 +257,76 @@ impl<T: Instance> RTC<T> {
 
                 let t = self.now();
                 if timestamp <= t {
-                    self.trigger_alarm();
+                    self.trigger_alarm(n);
                     return;
                 }
             } else {
-                self.rtc.intenclr.write(|w| w.compare1().clear());
+                self.clear_compare_int(n + 1);
             }
         })
     }
 
+    fn set_compare_int(&self, c: usize) {
+        self.rtc.intenset.write(|w| match c {
+            0 => w.compare0().set(),
+            1 => w.compare1().set(),
+            2 => w.compare2().set(),
+            3 => w.compare3().set(),
+            _ => unreachable!(),
+        });
+    }
+
+    fn clear_compare_int(&self, c: usize) {
+        self.rtc.intenclr.write(|w| match c {
+            0 => w.compare0().clear(),
+            1 => w.compare1().clear(),
+            2 => w.compare2().clear(),
+            3 => w.compare3().clear(),
+            _ => unreachable!(),
+        });
+    }
+
+    /// Register a callback fired whenever the 24-bit counter completes a full
+    /// 2^24-tick wrap (the rare true overflow, not the period midpoint).
+    pub fn set_overflow_callback(&self, callback: fn()) {
+        interrupt::free(|cs| self.overflow_callback.borrow(cs).set(Some(callback)));
+    }
+
     pub fn alarm0(&'static self) -> Alarm<T> {
-        Alarm { rtc: self }
+        self.alarm_n(0)
+    }
+
+    pub fn alarm1(&'static self) -> Alarm<T> {
+        self.alarm_n(1)
+    }
+
+    #[cfg(any(feature = "52832", feature = "52833", feature = "52840"))]
+    pub fn alarm2(&'static self) -> Alarm<T> {
+        self.alarm_n(2)
+    }
+
+    fn alarm_n(&'static self, n: usize) -> Alarm<T> {
+        assert!(
+            n < T::ALARM_COUNT,
+            "this RTC instance only has {} alarm(s)",
+            T::ALARM_COUNT
+        );
+        Alarm { n, rtc: self }
     }
 }
 
 pub struct Alarm<T: Instance> {
+    n: usize,
     rtc: &'static RTC<T>,
 }
 
 impl<T: Instance> embassy::time::Alarm for Alarm<T> {
     fn set(&self, timestamp: u64, callback: fn()) {
-        self.rtc.do_set_alarm(timestamp, Some(callback));
+        self.rtc.do_set_alarm(self.n, timestamp, Some(callback));
     }
 
     fn clear(&self) {
-        self.rtc.do_set_alarm(u64::MAX, None);
+        self.rtc.do_set_alarm(self.n, u64::MAX, None);
     }
 }
 
@@ -188,22 +335,31 @@ pub trait Instance: Deref<Target = rtc0::RegisterBlock> + Sized + 'static {
     /// The interrupt associated with this RTC instance.
     const INTERRUPT: Interrupt;
 
+    /// Number of independent alarms this instance exposes, bounded by its spare
+    /// compare channels (cc[0] is always reserved for the period midpoint).
+    const ALARM_COUNT: usize;
+
     fn set_rtc_instance(rtc: &'static RTC<Self>);
     fn get_rtc_instance() -> &'static RTC<Self>;
+    fn clear_rtc_instance();
 }
 
 macro_rules! impl_instance {
-    ($name:ident, $static_name:ident) => {
+    ($name:ident, $static_name:ident, $alarm_count:expr) => {
         static mut $static_name: Option<&'static RTC<$name>> = None;
 
         impl Instance for $name {
             const INTERRUPT: Interrupt = Interrupt::$name;
+            const ALARM_COUNT: usize = $alarm_count;
             fn set_rtc_instance(rtc: &'static RTC<Self>) {
                 unsafe { $static_name = Some(rtc) }
             }
             fn get_rtc_instance() -> &'static RTC<Self> {
                 unsafe { $static_name.unwrap() }
             }
+            fn clear_rtc_instance() {
+                unsafe { $static_name = None }
+            }
         }
 
         #[interrupt]
@@ -213,8 +369,9 @@ macro_rules! impl_instance {
     };
 }
 
-impl_instance!(RTC0, RTC0_INSTANCE);
-impl_instance!(RTC1, RTC1_INSTANCE);
+// RTC0 has three CC registers (cc[0] midpoint + two alarms); RTC1/RTC2 have four.
+impl_instance!(RTC0, RTC0_INSTANCE, 2);
+impl_instance!(RTC1, RTC1_INSTANCE, 3);
 
 #[cfg(any(feature = "52832", feature = "52833", feature = "52840"))]
-impl_instance!(RTC2, RTC2_INSTANCE);
\ No newline at end of file
+impl_instance!(RTC2, RTC2_INSTANCE, 3);